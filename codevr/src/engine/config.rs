@@ -0,0 +1,25 @@
+//! User-facing engine configuration, loaded once at startup and shared
+//! read-only (`Arc<Config>`) with every subsystem that needs it.
+
+pub struct Config {
+    pub window: WindowConfig,
+    pub graphics: GraphicsConfig,
+}
+
+pub struct WindowConfig {
+    pub resolution: [u32; 2],
+    pub fullscreen: bool,
+    // Index into `get_available_monitors()` to start fullscreen on. `None`
+    // (or an out-of-range index) falls back to the primary monitor.
+    pub monitor: Option<usize>,
+}
+
+pub struct GraphicsConfig {
+    pub vsync: bool,
+    // Device to use, matched against `PhysicalDevice::index()` (as a string)
+    // or `PhysicalDevice::name()`. `None` falls back to automatic selection.
+    pub preferred_device: Option<String>,
+    // Record the frame's secondary command buffers on `Renderer`'s recording
+    // pool instead of directly on the calling thread.
+    pub threaded_recording: bool,
+}
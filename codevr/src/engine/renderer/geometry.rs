@@ -0,0 +1,117 @@
+//! Minimal mesh-rendering subsystem: a vertex type, the shader pair that
+//! consumes it, and the pipeline that binds them to a render pass.
+
+use std::sync::Arc;
+
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::device::{Device, Queue};
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::pipeline::blend::Blend;
+use vulkano::pipeline::depth_stencil::DepthStencil;
+use vulkano::pipeline::input_assembly::InputAssembly;
+use vulkano::pipeline::multisample::Multisample;
+use vulkano::pipeline::vertex::SingleBufferDefinition;
+use vulkano::pipeline::viewport::{Scissor, Viewport, ViewportsState};
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineParams};
+
+use super::render_pass;
+
+#[derive(Copy, Clone)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+impl_vertex!(Vertex, position, color);
+
+pub type GeometryPipeline =
+    GraphicsPipeline<SingleBufferDefinition<Vertex>, vs::MainInput, vs::Layout>;
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 color;
+
+layout(location = 0) out vec3 v_color;
+
+void main() {
+    v_color = color;
+    gl_Position = vec4(position, 1.0);
+}
+"
+    }
+}
+
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec3 v_color;
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    f_color = vec4(v_color, 1.0);
+}
+"
+    }
+}
+
+/// A single colored triangle, used as the first milestone of geometry the
+/// renderer can push through the pipeline before `Scene` feeds real meshes.
+pub fn placeholder_triangle() -> [Vertex; 3] {
+    [
+        Vertex { position: [-0.5, 0.5, 0.0], color: [1.0, 0.0, 0.0] },
+        Vertex { position: [0.0, -0.5, 0.0], color: [0.0, 1.0, 0.0] },
+        Vertex { position: [0.5, 0.5, 0.0], color: [0.0, 0.0, 1.0] },
+    ]
+}
+
+pub fn upload_vertices(device: &Arc<Device>,
+                        queue: &Arc<Queue>,
+                        vertices: &[Vertex])
+                        -> Arc<CpuAccessibleBuffer<[Vertex]>> {
+    CpuAccessibleBuffer::from_iter(device,
+                                   &BufferUsage::vertex_buffer(),
+                                   Some(queue.family()),
+                                   vertices.iter().cloned())
+        .expect("failed to create vertex buffer")
+}
+
+pub fn build_pipeline(device: &Arc<Device>,
+                      render_pass: &Arc<render_pass::CustomRenderPass>,
+                      dimensions: [u32; 2])
+                      -> Arc<GeometryPipeline> {
+    let vs = vs::Shader::load(device.clone()).expect("failed to create vertex shader module");
+    let fs = fs::Shader::load(device.clone()).expect("failed to create fragment shader module");
+
+    Arc::new(GraphicsPipeline::new(device.clone(),
+                                    GraphicsPipelineParams {
+                                        vertex_input: SingleBufferDefinition::new(),
+                                        vertex_shader: vs.main_entry_point(),
+                                        input_assembly: InputAssembly::triangle_list(),
+                                        tessellation: None,
+                                        geometry_shader: None,
+                                        viewport: ViewportsState::Fixed {
+                                            data: vec![(Viewport {
+                                                            origin: [0.0, 0.0],
+                                                            dimensions: [dimensions[0] as f32,
+                                                                         dimensions[1] as f32],
+                                                            depth_range: 0.0..1.0,
+                                                        },
+                                                        Scissor::irrelevant())],
+                                        },
+                                        raster: Default::default(),
+                                        multisample: Multisample::disabled(),
+                                        fragment_shader: fs.main_entry_point(),
+                                        depth_stencil: DepthStencil::disabled(),
+                                        blend: Blend::pass_through(),
+                                        render_pass: Subpass::from(render_pass.clone(), 0).unwrap(),
+                                    })
+            .expect("failed to create graphics pipeline"))
+}
@@ -1,20 +1,27 @@
 mod text;
+mod geometry;
+mod font;
+mod thread_pool;
 
-use winit::{WindowBuilder, get_available_monitors, get_primary_monitor, Event, ElementState};
+use winit::{WindowBuilder, MonitorId, get_available_monitors, get_primary_monitor, Event,
+           ElementState};
 use vulkano_win::{Window, VkSurfaceBuild, required_extensions};
-use vulkano::instance::{Instance, PhysicalDevice};
+use vulkano::instance::{Instance, PhysicalDevice, PhysicalDeviceType};
 use vulkano::device::{Queue, Device, DeviceExtensions};
-use vulkano::swapchain::{Swapchain, SurfaceTransform, PresentMode};
+use vulkano::swapchain::{Swapchain, SurfaceTransform, PresentMode, AcquireError};
 use vulkano::image::SwapchainImage;
 use vulkano::image::attachment::AttachmentImage;
-use vulkano::framebuffer::Framebuffer;
-use vulkano::command_buffer::{PrimaryCommandBufferBuilder, Submission, submit};
+use vulkano::framebuffer::{Framebuffer, Subpass};
+use vulkano::command_buffer::{PrimaryCommandBufferBuilder, SecondaryGraphicsCommandBufferBuilder,
+                              CommandBuffer, Submission, DynamicState, submit};
+use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::pipeline::viewport::Viewport;
 use vulkano::format;
 
 use std::clone::Clone;
 use std::sync::Arc;
 use std::time::Duration;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use engine::config::Config;
 
 mod render_pass {
@@ -53,8 +60,19 @@ pub struct Renderer {
     depth_buffer: Arc<AttachmentImage<format::D16Unorm>>,
     render_pass: Arc<render_pass::CustomRenderPass>,
     framebuffers: Vec<Arc<Framebuffer<render_pass::CustomRenderPass>>>,
-    submissions: Vec<Arc<Submission>>,
-    queue: Arc<Queue>
+    // Submissions for frames that may still be executing on the GPU, oldest first.
+    // Bounded to `images.len()` so we never have more frames in flight than there
+    // are swapchain images to write into.
+    frames_in_flight: VecDeque<Arc<Submission>>,
+    queue: Arc<Queue>,
+    recreate_swapchain: bool,
+    geometry_pipeline: Arc<geometry::GeometryPipeline>,
+    vertex_buffer: Arc<CpuAccessibleBuffer<[geometry::Vertex]>>,
+    hud: text::Hud,
+    fullscreen: bool,
+    // Persistent workers used by `record_threaded`; recording a frame only
+    // submits jobs to these, it never spawns a thread itself.
+    recording_pool: thread_pool::ThreadPool
 }
 
 impl Renderer {
@@ -67,14 +85,19 @@ impl Renderer {
 
         let ins = &instance.clone();
 
-        let physical = PhysicalDevice::enumerate(&ins)
-            .next()
-            .expect("No vulkan device is available.");
-
-        let physical_device = physical.index();
+        let window_builder = if config.window.fullscreen {
+            window_builder.with_fullscreen(select_monitor(config.window.monitor))
+        } else {
+            window_builder
+        };
 
         let window = Arc::new(window_builder.build_vk_surface(&instance).unwrap());
 
+        let physical = pick_physical_device(ins, &window, &config)
+            .expect("No suitable vulkan device is available.");
+
+        let physical_device = physical.index();
+
         let queue = physical
             .queue_families()
             .find(|q| q.supports_graphics() && window.surface().is_supported(q).unwrap_or(false))
@@ -129,8 +152,24 @@ impl Renderer {
             })
             .collect::<Vec<_>>();
 
-        // Queue Submissions
-        let submissions = Vec::new();
+        // Submissions for frames still in flight on the GPU.
+        let frames_in_flight = VecDeque::with_capacity(images.len());
+
+        let geometry_pipeline =
+            geometry::build_pipeline(&device, &render_pass, images[0].dimensions());
+
+        let vertex_buffer =
+            geometry::upload_vertices(&device, &queue, &geometry::placeholder_triangle());
+
+        let hud = text::Hud::new(&device, &queue, &render_pass);
+
+        let fullscreen = config.window.fullscreen;
+
+        // Two workers is enough for the two secondary buffers `record_threaded`
+        // currently records (scene, HUD); it's a small fixed-size pool rather
+        // than one thread per recording so repeated frames don't keep paying
+        // OS thread creation/teardown cost.
+        let recording_pool = thread_pool::ThreadPool::new(2);
 
         (Renderer {
             instance,
@@ -141,14 +180,51 @@ impl Renderer {
             depth_buffer,
             framebuffers,
             render_pass,
-            submissions,
+            frames_in_flight,
             queue,
+            recreate_swapchain: false,
+            geometry_pipeline,
+            vertex_buffer,
+            hud,
+            fullscreen,
+            recording_pool,
             window: window.clone(),
             config
         }, window)
     }
 
+    /// Toggles between windowed and fullscreen at runtime, then rebuilds the
+    /// swapchain and framebuffers to match (reusing `resize()`).
+    ///
+    /// If the renderer is currently windowed, this claims `monitor_index`
+    /// (defaulting to the primary monitor when `None`) and goes fullscreen at
+    /// its native resolution. If it's already fullscreen, this drops back to
+    /// windowed instead, ignoring `monitor_index` — so a single hotkey can
+    /// just call `set_fullscreen(None)` (or whatever index it last used) to
+    /// flip back and forth.
+    pub fn set_fullscreen(&mut self, monitor_index: Option<usize>) {
+        if self.fullscreen {
+            self.window.window().set_fullscreen(None);
+            self.fullscreen = false;
+        } else {
+            let monitor = match monitor_index {
+                Some(index) => select_monitor(Some(index)),
+                None => get_primary_monitor(),
+            };
+
+            self.window.window().set_fullscreen(Some(monitor));
+            self.fullscreen = true;
+        }
+
+        self.resize();
+    }
+
     pub fn resize(&mut self) {
+                    // The old swapchain's images must not be in use when it is dropped.
+                    for submission in self.frames_in_flight.drain(..) {
+                        submission.destroy();
+                    }
+
                     let (swapchain, images) =
                         create_swapchain(&self.window, 
                                          &PhysicalDevice::from_index(&self.instance, self.physical_device).unwrap(),
@@ -175,36 +251,245 @@ impl Renderer {
                                     .unwrap()
                         })
                         .collect::<Vec<_>>();
+                    self.geometry_pipeline = geometry::build_pipeline(&self.device,
+                                                                      &self.render_pass,
+                                                                      self.images[0].dimensions());
+    }
+
+    /// Queues `content` to be drawn over the 3D scene on the current frame,
+    /// at `position` (normalized device coordinates) and `scale` (cell size
+    /// in the same units).
+    pub fn draw_text(&mut self, content: &str, position: [f32; 2], scale: f32) {
+        self.hud.queue(content, position, scale);
     }
 
     pub fn render(&mut self) {
-                let command_buffers = self.framebuffers
-            .iter()
-            .map(|framebuffer| {
-                PrimaryCommandBufferBuilder::new(&self.device, self.queue.family())
-                    .draw_inline(&self.render_pass,
-                                 &framebuffer,
-                                 render_pass::ClearValues {
-                                     color: [0.2, 0.4, 0.8, 1.0],
-                                     depth: 1.0,
-                                 })
-                    .draw_end()
+        if self.recreate_swapchain {
+            self.resize();
+            self.recreate_swapchain = false;
+        }
+
+        let image_num = match self.swapchain.acquire_next_image(Duration::new(1, 0)) {
+            Ok(image_num) => image_num,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                // This frame is being dropped, so nothing will ever flush the HUD
+                // strings queued for it; drop them too instead of letting them pile
+                // up (and get drawn again) on the next successful frame.
+                self.hud.clear();
+                return;
+            }
+            Err(err) => panic!("{:?}", err),
+        };
+
+        let dimensions = self.images[0].dimensions();
+        let hud_viewport = DynamicState {
+            viewports: Some(vec![Viewport {
+                                      origin: [0.0, 0.0],
+                                      dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+                                      depth_range: 0.0..1.0,
+                                  }]),
+            ..DynamicState::none()
+        };
+        let hud_vertices = self.hud.flush(&self.device, &self.queue);
+
+        // Only the framebuffer we just acquired will be presented this frame, so
+        // that's the only one worth (re)recording.
+        let command_buffer = if self.config.graphics.threaded_recording {
+            self.record_threaded(image_num, &hud_vertices, &hud_viewport)
+        } else {
+            self.record_single_threaded(image_num, &hud_vertices, &hud_viewport)
+        };
+
+        // Once `images.len()` frames are in flight, destroy the oldest (waiting for
+        // it if the GPU is still busy with it) before handing the GPU more work, so
+        // we never keep more than `images.len()` frames in flight.
+        while self.frames_in_flight.len() >= self.images.len() {
+            self.frames_in_flight.pop_front().unwrap().destroy();
+        }
+
+        self.frames_in_flight
+            .push_back(submit(&*command_buffer, &self.queue).unwrap());
+
+        match self.swapchain.present(&self.queue, image_num) {
+            Ok(()) => {}
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+            }
+            Err(err) => panic!("{:?}", err),
+        }
+    }
+
+    /// Records the frame's primary command buffer directly on the calling
+    /// thread. Kept as the default and as a correctness baseline to validate
+    /// `record_threaded` against.
+    fn record_single_threaded(&self,
+                              image_num: usize,
+                              hud_vertices: &Option<Arc<CpuAccessibleBuffer<[text::GlyphVertex]>>>,
+                              hud_viewport: &DynamicState)
+                              -> Box<CommandBuffer> {
+        let builder = PrimaryCommandBufferBuilder::new(&self.device, self.queue.family())
+            .draw_inline(&self.render_pass,
+                         &self.framebuffers[image_num],
+                         render_pass::ClearValues {
+                             color: [0.2, 0.4, 0.8, 1.0],
+                             depth: 1.0,
+                         })
+            .draw(&self.geometry_pipeline,
+                  &self.vertex_buffer,
+                  &DynamicState::none(),
+                  (),
+                  &());
+
+        let builder = match *hud_vertices {
+            Some(ref vertices) => {
+                builder.draw(self.hud.pipeline(),
+                             vertices,
+                             hud_viewport,
+                             self.hud.atlas_set().clone(),
+                             &())
+            }
+            None => builder,
+        };
+
+        Box::new(builder.draw_end().build())
+    }
+
+    /// Records the scene and HUD draws as secondary command buffers on the
+    /// renderer's recording pool, then stitches them into a single primary
+    /// command buffer on the calling (main) thread and returns it; `render()`
+    /// submits it.
+    fn record_threaded(&self,
+                       image_num: usize,
+                       hud_vertices: &Option<Arc<CpuAccessibleBuffer<[text::GlyphVertex]>>>,
+                       hud_viewport: &DynamicState)
+                       -> Box<CommandBuffer> {
+        let subpass = Subpass::from(self.render_pass.clone(), 0).unwrap();
+
+        let geometry_secondary = {
+            let device = self.device.clone();
+            let queue_family = self.queue.family();
+            let subpass = subpass.clone();
+            let pipeline = self.geometry_pipeline.clone();
+            let vertex_buffer = self.vertex_buffer.clone();
+
+            self.recording_pool.execute(move || {
+                SecondaryGraphicsCommandBufferBuilder::new(&device, queue_family, subpass)
+                    .draw(&pipeline, &vertex_buffer, &DynamicState::none(), (), &())
                     .build()
             })
-            .collect::<Vec<_>>();
-        let image_num = self.swapchain
-            .acquire_next_image(Duration::new(1, 0))
-            .unwrap();
+        };
 
-        // @TODO build command buffers with threads and submit the changes in main thread (here)
-        self.submissions
-            .push(submit(&command_buffers[image_num], &self.queue).unwrap());
+        let hud_secondary = hud_vertices.clone().map(|vertices| {
+            let device = self.device.clone();
+            let queue_family = self.queue.family();
+            let subpass = subpass.clone();
+            let pipeline = self.hud.pipeline().clone();
+            let atlas_set = self.hud.atlas_set().clone();
+            let viewport = hud_viewport.clone();
+
+            self.recording_pool.execute(move || {
+                SecondaryGraphicsCommandBufferBuilder::new(&device, queue_family, subpass)
+                    .draw(&pipeline, &vertices, &viewport, atlas_set, &())
+                    .build()
+            })
+        });
+
+        let geometry_secondary = geometry_secondary.recv()
+            .expect("geometry recording worker panicked");
+        let hud_secondary = hud_secondary.map(|receiver| {
+            receiver.recv().expect("HUD recording worker panicked")
+        });
+
+        let builder = PrimaryCommandBufferBuilder::new(&self.device, self.queue.family())
+            .draw_inline(&self.render_pass,
+                         &self.framebuffers[image_num],
+                         render_pass::ClearValues {
+                             color: [0.2, 0.4, 0.8, 1.0],
+                             depth: 1.0,
+                         })
+            .execute_commands(geometry_secondary);
+
+        let builder = match hud_secondary {
+            Some(secondary) => builder.execute_commands(secondary),
+            None => builder,
+        };
 
-        self.swapchain.present(&self.queue, image_num).unwrap();
+        Box::new(builder.draw_end().build())
     }
 }
 
 
+/// Picks a monitor by index into `get_available_monitors()`, falling back to
+/// the primary monitor when `index` is `None` or out of range.
+fn select_monitor(index: Option<usize>) -> MonitorId {
+    let monitors = get_available_monitors().collect::<Vec<_>>();
+
+    index
+        .and_then(|i| monitors.into_iter().nth(i))
+        .unwrap_or_else(get_primary_monitor)
+}
+
+/// Picks the physical device the renderer should use.
+///
+/// Honours `config.graphics.preferred_device` (by name or index) when set; otherwise
+/// enumerates every device exposing a graphics+present queue family and the
+/// `khr_swapchain` extension and ranks the survivors discrete > integrated > virtual
+/// > CPU, logging the final choice.
+fn pick_physical_device<'a>(instance: &'a Arc<Instance>,
+                            window: &Window,
+                            config: &Config)
+                            -> Option<PhysicalDevice<'a>> {
+    let mut candidates = PhysicalDevice::enumerate(instance)
+        .filter(|physical| {
+            let has_present_queue = physical
+                .queue_families()
+                .any(|q| q.supports_graphics() && window.surface().is_supported(&q).unwrap_or(false));
+
+            let has_swapchain_ext = DeviceExtensions::supported_by_device(*physical).khr_swapchain;
+
+            has_present_queue && has_swapchain_ext
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(ref preferred) = config.graphics.preferred_device {
+        let chosen = candidates.iter().find(|physical| {
+            preferred.parse::<usize>().map(|i| i == physical.index()).unwrap_or(false) ||
+            physical.name() == preferred.as_str()
+        }).cloned();
+
+        if let Some(physical) = chosen {
+            println!("Using preferred GPU: {} ({:?})", physical.name(), physical.ty());
+            return Some(physical);
+        }
+
+        println!("Preferred GPU '{}' not found or unsuitable, falling back to auto-selection.",
+                 preferred);
+    }
+
+    candidates.sort_by_key(|physical| device_type_rank(physical.ty()));
+
+    let physical = candidates.into_iter().next();
+
+    if let Some(physical) = physical {
+        println!("Using GPU: {} ({:?})", physical.name(), physical.ty());
+    }
+
+    physical
+}
+
+/// Lower is better: prefer discrete, then integrated, then virtual, then a CPU
+/// rasterizer, and any other device type last.
+fn device_type_rank(ty: PhysicalDeviceType) -> u32 {
+    match ty {
+        PhysicalDeviceType::DiscreteGpu => 0,
+        PhysicalDeviceType::IntegratedGpu => 1,
+        PhysicalDeviceType::VirtualGpu => 2,
+        PhysicalDeviceType::Cpu => 3,
+        PhysicalDeviceType::Other => 4,
+    }
+}
+
 /// Sets up and creates a swapchain
 fn create_swapchain(window: &Window,
                     physical_device: &PhysicalDevice,
@@ -222,21 +507,18 @@ fn create_swapchain(window: &Window,
             
 
 
-        let dimensions = if config.window.resolution[0] <= 240 ||
-                            config.window.resolution[1] <= 240 {
+        // Prefer the surface's own current extent (the only value that is correct
+        // immediately after a resize/DPI change/minimize-restore); only fall back to
+        // the configured resolution when the platform doesn't report one.
+        let dimensions = match caps.current_extent {
+            Some(extent) => {
+                let min = caps.min_image_extent;
+                let max = caps.max_image_extent;
 
-            let min = caps.min_image_extent;
-
-            let extent = caps.current_extent.unwrap_or([800, 600]);
-
-            if extent[0] < min[0] || extent[1] < min[1] {
-                min
+                [extent[0].max(min[0]).min(max[0]),
+                 extent[1].max(min[1]).min(max[1])]
             }
-            else {
-                extent
-            }
-        } else {
-            config.window.resolution
+            None => config.window.resolution,
         };
 
 
@@ -0,0 +1,256 @@
+//! On-screen HUD overlay: rasterizes queued strings into screen-space quads
+//! sampling a glyph atlas, drawn on top of the 3D scene with depth testing
+//! disabled. Used for frame time, device name, and VR pose status readouts.
+
+use std::sync::Arc;
+
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::submit;
+use vulkano::descriptor::descriptor_set::DescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::format::R8Unorm;
+use vulkano::framebuffer::Subpass;
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::image::Dimensions;
+use vulkano::pipeline::blend::Blend;
+use vulkano::pipeline::depth_stencil::DepthStencil;
+use vulkano::pipeline::input_assembly::InputAssembly;
+use vulkano::pipeline::multisample::Multisample;
+use vulkano::pipeline::vertex::SingleBufferDefinition;
+use vulkano::pipeline::viewport::{Scissor, Viewport, ViewportsState};
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineParams};
+use vulkano::sampler::Sampler;
+
+use super::font;
+use super::render_pass;
+
+/// Glyph atlas layout: one cell per code point from 32 (space) to 127, 16
+/// columns by 6 rows.
+const ATLAS_COLUMNS: u32 = 16;
+const ATLAS_ROWS: u32 = 6;
+const GLYPH_SIZE: u32 = 8;
+
+#[derive(Copy, Clone)]
+pub struct GlyphVertex {
+    pub screen_position: [f32; 2],
+    pub uv: [f32; 2],
+}
+
+impl_vertex!(GlyphVertex, screen_position, uv);
+
+pub type HudPipeline =
+    GraphicsPipeline<SingleBufferDefinition<GlyphVertex>, vs::MainInput, vs::Layout>;
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 screen_position;
+layout(location = 1) in vec2 uv;
+
+layout(location = 0) out vec2 v_uv;
+
+void main() {
+    v_uv = uv;
+    gl_Position = vec4(screen_position, 0.0, 1.0);
+}
+"
+    }
+}
+
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+#version 450
+
+layout(location = 0) in vec2 v_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D atlas;
+
+void main() {
+    float alpha = texture(atlas, v_uv).r;
+    f_color = vec4(1.0, 1.0, 1.0, alpha);
+}
+"
+    }
+}
+
+/// A string queued for the current frame by `Renderer::draw_text`.
+struct QueuedText {
+    content: String,
+    position: [f32; 2],
+    scale: f32,
+}
+
+/// Owns the glyph atlas and the pipeline used to draw it, plus the strings
+/// queued for the frame currently being built.
+pub struct Hud {
+    pipeline: Arc<HudPipeline>,
+    atlas_set: Arc<DescriptorSet + Send + Sync>,
+    queued: Vec<QueuedText>,
+}
+
+impl Hud {
+    pub fn new(device: &Arc<Device>,
+               queue: &Arc<Queue>,
+               render_pass: &Arc<render_pass::CustomRenderPass>)
+               -> Hud {
+        let pipeline = build_pipeline(device, render_pass);
+        let atlas_set = build_atlas(device, queue, pipeline.clone());
+
+        Hud { pipeline, atlas_set, queued: Vec::new() }
+    }
+
+    /// Queues `content` to be drawn at `position` (in normalized device
+    /// coordinates) at the end of the current frame.
+    pub fn queue(&mut self, content: &str, position: [f32; 2], scale: f32) {
+        self.queued.push(QueuedText {
+            content: content.to_owned(),
+            position,
+            scale,
+        });
+    }
+
+    /// Builds the vertex buffer for every string queued this frame and clears
+    /// the queue for the next one. Returns `None` when nothing was queued.
+    pub fn flush(&mut self,
+                 device: &Arc<Device>,
+                 queue: &Arc<Queue>)
+                 -> Option<Arc<CpuAccessibleBuffer<[GlyphVertex]>>> {
+        if self.queued.is_empty() {
+            return None;
+        }
+
+        let mut vertices = Vec::new();
+
+        for text in self.queued.drain(..) {
+            for (i, glyph) in text.content.chars().enumerate() {
+                vertices.extend_from_slice(&glyph_quad(glyph, text.position, text.scale, i));
+            }
+        }
+
+        Some(CpuAccessibleBuffer::from_iter(device,
+                                            &BufferUsage::vertex_buffer(),
+                                            Some(queue.family()),
+                                            vertices.into_iter())
+                 .expect("failed to create HUD vertex buffer"))
+    }
+
+    /// Discards whatever strings were queued for a frame that's being
+    /// dropped (e.g. on an out-of-date swapchain), so they don't linger and
+    /// get drawn — possibly duplicated — on a later frame.
+    pub fn clear(&mut self) {
+        self.queued.clear();
+    }
+
+    pub fn pipeline(&self) -> &Arc<HudPipeline> {
+        &self.pipeline
+    }
+
+    pub fn atlas_set(&self) -> &Arc<DescriptorSet + Send + Sync> {
+        &self.atlas_set
+    }
+}
+
+/// Builds the two triangles for a single glyph quad, advancing one `scale`-wide
+/// cell to the right for each character already emitted on this string.
+fn glyph_quad(glyph: char, position: [f32; 2], scale: f32, index: usize) -> [GlyphVertex; 6] {
+    let code = (glyph as u32).saturating_sub(32).min(ATLAS_COLUMNS * ATLAS_ROWS - 1);
+    let cell_x = (code % ATLAS_COLUMNS) as f32 / ATLAS_COLUMNS as f32;
+    let cell_y = (code / ATLAS_COLUMNS) as f32 / ATLAS_ROWS as f32;
+    let cell_w = 1.0 / ATLAS_COLUMNS as f32;
+    let cell_h = 1.0 / ATLAS_ROWS as f32;
+
+    let x = position[0] + index as f32 * scale;
+    let y = position[1];
+
+    let top_left = GlyphVertex { screen_position: [x, y], uv: [cell_x, cell_y] };
+    let top_right = GlyphVertex { screen_position: [x + scale, y], uv: [cell_x + cell_w, cell_y] };
+    let bottom_left = GlyphVertex { screen_position: [x, y + scale], uv: [cell_x, cell_y + cell_h] };
+    let bottom_right = GlyphVertex {
+        screen_position: [x + scale, y + scale],
+        uv: [cell_x + cell_w, cell_y + cell_h],
+    };
+
+    [top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]
+}
+
+fn build_pipeline(device: &Arc<Device>,
+                  render_pass: &Arc<render_pass::CustomRenderPass>)
+                  -> Arc<HudPipeline> {
+    let vs = vs::Shader::load(device.clone()).expect("failed to create HUD vertex shader module");
+    let fs = fs::Shader::load(device.clone()).expect("failed to create HUD fragment shader module");
+
+    Arc::new(GraphicsPipeline::new(device.clone(),
+                                    GraphicsPipelineParams {
+                                        vertex_input: SingleBufferDefinition::new(),
+                                        vertex_shader: vs.main_entry_point(),
+                                        input_assembly: InputAssembly::triangle_list(),
+                                        tessellation: None,
+                                        geometry_shader: None,
+                                        viewport: ViewportsState::Dynamic,
+                                        raster: Default::default(),
+                                        multisample: Multisample::disabled(),
+                                        fragment_shader: fs.main_entry_point(),
+                                        // The HUD draws after the 3D scene and must never be
+                                        // occluded by it or occlude it on the next frame.
+                                        depth_stencil: DepthStencil::disabled(),
+                                        blend: Blend::alpha_blending(),
+                                        render_pass: Subpass::from(render_pass.clone(), 0).unwrap(),
+                                    })
+            .expect("failed to create HUD pipeline"))
+}
+
+/// Rasterizes the built-in bitmap font (see `font::bitmap`) into one atlas
+/// cell per printable ASCII code point.
+fn rasterize_atlas(width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![0u8; (width * height) as usize];
+
+    for code in 0..(ATLAS_COLUMNS * ATLAS_ROWS) {
+        let glyph = ::std::char::from_u32(32 + code).unwrap_or(' ');
+        let bits = font::bitmap(glyph);
+
+        let cell_x = (code % ATLAS_COLUMNS) * GLYPH_SIZE;
+        let cell_y = (code / ATLAS_COLUMNS) * GLYPH_SIZE;
+
+        for (row, line) in bits.iter().enumerate() {
+            for col in 0..GLYPH_SIZE {
+                let lit = (line >> (GLYPH_SIZE - 1 - col as u32)) & 1 == 1;
+                let x = cell_x + col;
+                let y = cell_y + row as u32;
+                pixels[(y * width + x) as usize] = if lit { 255 } else { 0 };
+            }
+        }
+    }
+
+    pixels
+}
+
+/// Uploads the glyph atlas, blocking until the upload has actually completed
+/// on the GPU so the descriptor set below never reads from an image whose
+/// contents aren't valid yet.
+fn build_atlas(device: &Arc<Device>,
+              queue: &Arc<Queue>,
+              pipeline: Arc<HudPipeline>)
+              -> Arc<DescriptorSet + Send + Sync> {
+    let width = ATLAS_COLUMNS * GLYPH_SIZE;
+    let height = ATLAS_ROWS * GLYPH_SIZE;
+
+    let (image, init) = ImmutableImage::from_iter(rasterize_atlas(width, height).into_iter(),
+                                                  Dimensions::Dim2d { width, height },
+                                                  R8Unorm,
+                                                  queue.clone())
+        .expect("failed to upload glyph atlas");
+
+    submit(&init, queue).expect("failed to submit glyph atlas upload").destroy();
+
+    let sampler = Sampler::simple_repeat_linear_no_mipmap(device.clone());
+
+    Arc::new(simple_descriptor_set!(pipeline.clone(), 0, {
+        atlas: (image, sampler)
+    }))
+}
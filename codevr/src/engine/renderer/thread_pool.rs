@@ -0,0 +1,55 @@
+//! A small persistent worker pool for off-main-thread command buffer
+//! recording, so `Renderer::record_threaded` isn't paying OS thread
+//! creation/teardown cost on every frame.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Job = Box<FnOnce() + Send>;
+
+pub struct ThreadPool {
+    sender: mpsc::Sender<Job>,
+}
+
+impl ThreadPool {
+    /// Spawns `size` worker threads that live for the lifetime of the pool,
+    /// each pulling jobs off a shared queue.
+    pub fn new(size: usize) -> ThreadPool {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for _ in 0..size {
+            let receiver = receiver.clone();
+
+            thread::spawn(move || {
+                loop {
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
+
+        ThreadPool { sender }
+    }
+
+    /// Queues `job` on a worker thread and returns a handle to block on its
+    /// result, mirroring the shape of `thread::spawn(..).join()`.
+    pub fn execute<F, T>(&self, job: F) -> mpsc::Receiver<T>
+        where F: FnOnce() -> T + Send + 'static,
+              T: Send + 'static
+    {
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        self.sender
+            .send(Box::new(move || {
+                let _ = result_sender.send(job());
+            }))
+            .expect("thread pool worker threads have all shut down");
+
+        result_receiver
+    }
+}